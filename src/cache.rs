@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+
+use crate::providers::{LrclibQuery, Lyrics, LyricsProvider};
+
+#[derive(Serialize, Deserialize, Clone)]
+enum CachedResult {
+    Get(Option<Lyrics>),
+    Search(Option<Vec<Lyrics>>),
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct CacheEntry {
+    cached_at_unix: u64,
+    result: CachedResult,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct CacheFile {
+    entries: HashMap<String, CacheEntry>,
+}
+
+fn default_cache_path() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("lrcsync")
+        .join("lyrics_cache.json")
+}
+
+fn now_unix() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+/// hashes the normalized, provider-scoped query fields so near-duplicate tags (case, whitespace)
+/// still hit the same cache entry
+fn query_key(provider_name: &str, query: &LrclibQuery) -> String {
+    let normalized = format!(
+        "{}|{}|{}|{}|{}",
+        provider_name,
+        query.track_name.trim().to_lowercase(),
+        query.artist_name.trim().to_lowercase(),
+        query.album_name.as_deref().unwrap_or("").trim().to_lowercase(),
+        query.duration.map(|d| d.round() as i64).unwrap_or(-1),
+    );
+    let mut hasher = Sha256::new();
+    hasher.update(normalized.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+async fn load_cache_file(path: &Path) -> CacheFile {
+    match tokio::fs::read_to_string(path).await {
+        Ok(contents) => serde_json::from_str(&contents).unwrap_or_default(),
+        Err(_) => CacheFile::default(),
+    }
+}
+
+/// the on-disk cache state shared by every `CachingProvider` wrapping a provider in the chain,
+/// so a store from one provider can't clobber a concurrent store from another
+pub struct SharedCacheFile {
+    path: PathBuf,
+    file: Mutex<CacheFile>,
+}
+
+impl SharedCacheFile {
+    pub async fn load() -> Arc<Self> {
+        let path = default_cache_path();
+        let file = load_cache_file(&path).await;
+        Arc::new(Self { path, file: Mutex::new(file) })
+    }
+
+    async fn lookup(&self, key: &str, ttl: Duration) -> Option<CachedResult> {
+        let file = self.file.lock().await;
+        let entry = file.entries.get(key)?;
+        if now_unix().saturating_sub(entry.cached_at_unix) < ttl.as_secs() {
+            Some(entry.result.clone())
+        } else {
+            None
+        }
+    }
+
+    async fn store(&self, key: String, result: CachedResult) {
+        // hold the guard across the write: concurrent stores must serialize their
+        // read-modify-write of the cache file, or an older snapshot can overwrite a newer one
+        // (or two writers can interleave into truncated/corrupt JSON) when several providers
+        // store at once.
+        let mut file = self.file.lock().await;
+        file.entries.insert(key, CacheEntry { cached_at_unix: now_unix(), result });
+        let result = match serde_json::to_string(&*file) {
+            Ok(contents) => write_cache_file(&self.path, contents).await,
+            Err(err) => Err(err.into()),
+        };
+        if let Err(err) = result {
+            tracing::warn!(error = %err, path = %self.path.display(), "failed to persist lyrics cache");
+        }
+    }
+}
+
+async fn write_cache_file(path: &Path, contents: String) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(path, contents).await?;
+    Ok(())
+}
+
+/// wraps a `LyricsProvider` with a TTL'd on-disk cache, keyed by a hash of the query, so repeat
+/// syncs over the same library (including tracks that have no match) don't re-hit the network.
+/// All `CachingProvider`s in a run should share one `SharedCacheFile` so they read/write the same
+/// on-disk state instead of clobbering each other.
+pub struct CachingProvider {
+    inner: Box<dyn LyricsProvider>,
+    shared: Arc<SharedCacheFile>,
+    ttl: Duration,
+}
+
+impl CachingProvider {
+    pub fn wrap(inner: Box<dyn LyricsProvider>, shared: Arc<SharedCacheFile>, ttl: Duration) -> Self {
+        Self { inner, shared, ttl }
+    }
+}
+
+#[async_trait]
+impl LyricsProvider for CachingProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn get(&self, query: &LrclibQuery) -> anyhow::Result<Option<Lyrics>> {
+        let key = format!("get:{}", query_key(self.inner.name(), query));
+        if let Some(CachedResult::Get(cached)) = self.shared.lookup(&key, self.ttl).await {
+            return Ok(cached);
+        }
+        let result = self.inner.get(query).await?;
+        self.shared.store(key, CachedResult::Get(result.clone())).await;
+        Ok(result)
+    }
+
+    async fn search(&self, query: &LrclibQuery) -> anyhow::Result<Option<Vec<Lyrics>>> {
+        let key = format!("search:{}", query_key(self.inner.name(), query));
+        if let Some(CachedResult::Search(cached)) = self.shared.lookup(&key, self.ttl).await {
+            return Ok(cached);
+        }
+        let result = self.inner.search(query).await?;
+        self.shared.store(key, CachedResult::Search(result.clone())).await;
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(track_name: &str, artist_name: &str, album_name: &str, duration: Option<f32>) -> LrclibQuery {
+        LrclibQuery {
+            track_name: track_name.to_string(),
+            artist_name: artist_name.to_string(),
+            album_name: Some(album_name.to_string()),
+            duration,
+            tolerance: 5.0,
+        }
+    }
+
+    #[test]
+    fn query_key_is_case_and_whitespace_insensitive() {
+        let a = query("  Song Title ", "Some Artist", "An Album", Some(180.0));
+        let b = query("song title", "SOME ARTIST", "an album", Some(180.0));
+        assert_eq!(query_key("lrclib", &a), query_key("lrclib", &b));
+    }
+
+    #[test]
+    fn query_key_differs_per_provider_and_field() {
+        let a = query("Song Title", "Some Artist", "An Album", Some(180.0));
+        let b = query("Song Title", "Some Artist", "An Album", Some(181.0));
+        assert_ne!(query_key("lrclib", &a), query_key("lrclib", &b));
+        assert_ne!(query_key("lrclib", &a), query_key("other-provider", &a));
+    }
+}
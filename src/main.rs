@@ -1,11 +1,60 @@
 
+mod cache;
+mod musicbrainz;
+mod providers;
+mod publish;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
 use anyhow::bail;
 use audiotags::Tag;
 use clap::Parser;
+use futures::stream::{self, StreamExt};
 use ignore::{DirEntry, WalkBuilder};
-use serde::{Serialize, Deserialize};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use tokio::{fs::File, io::AsyncWriteExt};
 
+use cache::{CachingProvider, SharedCacheFile};
+use musicbrainz::MusicBrainzClient;
+use providers::{build_providers, LrclibQuery, Lyrics, LyricsProvider};
+use publish::LrcLibPublisher;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    Pretty,
+    Json,
+}
+
+impl std::fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogFormat::Pretty => write!(f, "pretty"),
+            LogFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// a `tracing` writer that routes log lines through `MultiProgress::println` instead of stderr
+/// directly, so `-v`/`-vv` output doesn't tear through the progress bar mid-redraw
+struct ProgressSafeWriter {
+    multi: MultiProgress,
+}
+
+impl std::io::Write for ProgressSafeWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        for line in String::from_utf8_lossy(buf).lines() {
+            let _ = self.multi.println(line);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 #[derive(Parser, Debug)]
 #[command(version = env!("CARGO_PKG_VERSION"), about = "pulls lrc files for songs in the current directory, try it on your music collection", long_about = None)]
 pub struct CliConfig {
@@ -21,305 +70,557 @@ pub struct CliConfig {
     pub search: bool,
     #[arg(short = 't', long = "tolerance", default_value_t = 5.0, help = "tolerance in seconds for searching lrclib")]
     pub tolerance: f32,
+    #[arg(short = 'j', long = "concurrency", default_value_t = 8, help = "number of files to process at the same time")]
+    pub concurrency: usize,
+    #[arg(short = 'e', long = "embed", default_value_t = false, help = "also embed found lyrics into the audio file's own tags (mp3, m4a, flac)")]
+    pub embed: bool,
+    #[arg(long = "embed-only", default_value_t = false, help = "embed lyrics into the audio file's tags (mp3, m4a, flac) and skip writing a sidecar .lrc")]
+    pub embed_only: bool,
+    #[arg(long = "providers", value_delimiter = ',', default_value = "lrclib", help = "ordered, comma separated list of lyric providers to try (currently: lrclib)")]
+    pub providers: Vec<String>,
+    #[arg(long = "cache-ttl", default_value_t = 604800, help = "how long (in seconds) cached provider responses, including misses, stay valid; 0 disables caching")]
+    pub cache_ttl: u64,
+    #[arg(long = "no-cache", default_value_t = false, help = "never read or write the on-disk lyrics cache")]
+    pub no_cache: bool,
+    #[arg(short = 'v', long = "verbose", action = clap::ArgAction::Count, help = "increase log verbosity (default: info, -v for debug, -vv for trace)")]
+    pub verbose: u8,
+    #[arg(long = "log-format", value_enum, default_value_t = LogFormat::Pretty, help = "log output format")]
+    pub log_format: LogFormat,
+    #[arg(long = "musicbrainz", default_value_t = false, help = "look up canonical metadata on MusicBrainz when local tags are missing or a lookup misses")]
+    pub musicbrainz: bool,
+    #[arg(long = "publish", default_value_t = false, help = "contribute an existing .lrc (and, once a non-lrclib provider exists, fallback-provider lyrics) back to lrclib")]
+    pub publish: bool,
+    #[arg(long = "publish-workers", default_value_t = 4, help = "number of threads used to solve lrclib's publish proof-of-work challenge")]
+    pub publish_workers: usize,
 }
 
-static DEFAULT_USER_AGENT: &str = concat!(
-    env!("CARGO_PKG_NAME"),
-    "/",
-    env!("CARGO_PKG_VERSION"),
-    " (",
-    // github repo
-    env!("CARGO_PKG_HOMEPAGE"),
-    ")"
-);
-
-pub struct LrcLibClient {
-    pub url: String,
-    pub client: reqwest::Client,
+pub async fn write_lrc_for_file(entry: &DirEntry, synced_lyrics: &str, config: &CliConfig) -> anyhow::Result<()> {
+    let lrc_path = entry.path().with_extension("lrc");
+
+    match File::create(lrc_path).await {
+        Ok(mut file) => {
+            match file.write_all(synced_lyrics.as_bytes()).await {
+                Ok(_) => {
+                    tracing::info!(path = %entry.path().display(), "wrote_lrc");
+                },
+                Err(err) => {
+                    bail!("Writing file failed: {}", err);
+                }
+            }
+        },
+        Err(err) => {
+            bail!("Creating file failed: {}", err);
+        }
+    }
+
+    Ok(())
 }
 
-pub struct LrclibQuery {
-    pub track_name: String,
-    pub artist_name: String,
-    pub album_name: Option<String>,
-    pub duration: Option<f32>,
+// embeds lyrics into the audio file's own tags and saves in place, the way a music manager
+// embeds art instead of leaving it loose. audiotags has no concept of lyrics at all, so this
+// writes the format-specific frame directly rather than going through `Tag`/`AudioTag`: ID3v2
+// (mp3) has dedicated synced (SYLT) and unsynced (USLT) lyrics frames; mp4 (m4a) and FLAC only
+// have a conventional slot for unsynced text, so those containers get the plain lyrics with the
+// sync timing dropped rather than being rejected outright.
+pub fn embed_lyrics_for_file(entry: &DirEntry, lyrics: &Lyrics, config: &CliConfig) -> anyhow::Result<()> {
+    if lyrics.synced.is_none() && lyrics.plain.is_none() {
+        bail!("No lyrics available to embed");
+    }
+
+    match entry.path().extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("mp3") => embed_id3_lyrics(entry.path(), lyrics)?,
+        Some(ext) if ext.eq_ignore_ascii_case("m4a") => embed_mp4_lyrics(entry.path(), lyrics)?,
+        Some(ext) if ext.eq_ignore_ascii_case("flac") => embed_flac_lyrics(entry.path(), lyrics)?,
+        Some(ext) => bail!("embedding lyrics into .{} files is not supported yet", ext),
+        None => bail!("cannot embed lyrics: file has no extension"),
+    }
+
+    tracing::info!(path = %entry.path().display(), "embedded_lyrics");
+    let _ = config;
+    Ok(())
 }
 
-impl LrclibQuery {
-    // old method
-    pub fn to_get_query_string(&self) -> String {
-        let mut query = String::new();
-        query.push_str("track_name=");
-        query.push_str(&self.track_name);
-        query.push_str("&artist_name=");
-        query.push_str(&self.artist_name);
-        if let Some(album_name) = &self.album_name {
-            query.push_str("&album_name=");
-            query.push_str(album_name);
-        }
-        if let Some(duration) = &self.duration {
-            query.push_str("&duration=");
-            query.push_str(&duration.to_string());
+/// writes both the unsynced (USLT) and, when we have timed lines, synced (SYLT) ID3v2 lyrics
+/// frames, replacing whatever lyrics frames were already on the file
+fn embed_id3_lyrics(path: &std::path::Path, lyrics: &Lyrics) -> anyhow::Result<()> {
+    use id3::frame::{Lyrics as UnsyncLyricsFrame, SynchronisedLyrics, SynchronisedLyricsType, TimestampFormat};
+    use id3::{Tag, TagLike, Version};
+
+    let mut tag = match Tag::read_from_path(path) {
+        Ok(tag) => tag,
+        Err(id3::Error { kind: id3::ErrorKind::NoTag, .. }) => Tag::new(),
+        Err(err) => return Err(err.into()),
+    };
+
+    tag.remove("USLT");
+    tag.remove("SYLT");
+
+    let plain_text = lyrics.plain.as_deref().or(lyrics.synced.as_deref()).unwrap_or_default();
+    tag.add_frame(UnsyncLyricsFrame {
+        lang: "eng".to_string(),
+        description: String::new(),
+        text: plain_text.to_string(),
+    });
+
+    if let Some(synced) = lyrics.synced.as_deref() {
+        if let Some(content) = parse_lrc_timestamps(synced) {
+            tag.add_frame(SynchronisedLyrics {
+                lang: "eng".to_string(),
+                timestamp_format: TimestampFormat::Ms,
+                content_type: SynchronisedLyricsType::Lyrics,
+                description: String::new(),
+                content,
+            });
         }
-        query
     }
 
-    pub fn to_get_query(&self) -> Vec<(String, String)> {
-        let mut query = self.to_search_query();
-        if let Some(duration) = &self.duration {
-            query.push(("duration".to_string(), duration.to_string()));
-        }
-        query
+    tag.write_to_path(path, Version::Id3v24)?;
+    Ok(())
+}
+
+/// writes the `©lyr` freeform atom mp4 taggers use for lyrics. mp4 has no standard synced-lyrics
+/// atom, so this always writes plain text and drops any timing info.
+fn embed_mp4_lyrics(path: &std::path::Path, lyrics: &Lyrics) -> anyhow::Result<()> {
+    let mut tag = mp4ameta::Tag::read_from_path(path)?;
+    let plain_text = match lyrics.plain.as_deref() {
+        Some(plain) => plain.to_string(),
+        None => strip_lrc_timestamps(lyrics.synced.as_deref().unwrap_or_default()),
+    };
+    tag.set_lyrics(plain_text);
+    tag.write_to_path(path)?;
+    Ok(())
+}
+
+/// writes the `LYRICS` Vorbis comment metaflac and other taggers use for FLAC lyrics. FLAC has no
+/// standard synced-lyrics comment either, so this always writes plain text and drops any timing.
+fn embed_flac_lyrics(path: &std::path::Path, lyrics: &Lyrics) -> anyhow::Result<()> {
+    let mut tag = metaflac::Tag::read_from_path(path)?;
+    let plain_text = match lyrics.plain.as_deref() {
+        Some(plain) => plain.to_string(),
+        None => strip_lrc_timestamps(lyrics.synced.as_deref().unwrap_or_default()),
+    };
+    tag.vorbis_comments_mut().set("LYRICS", vec![plain_text]);
+    tag.write_to_path(path)?;
+    Ok(())
+}
+
+/// parses `[mm:ss.xx] line` LRC text into `(offset_ms, line)` pairs for an ID3 SYLT frame,
+/// dropping lines that don't carry a leading timestamp
+fn parse_lrc_timestamps(lrc: &str) -> Option<Vec<(u32, String)>> {
+    let mut content = Vec::new();
+    for line in lrc.lines() {
+        let line = line.trim();
+        let Some(close) = line.strip_prefix('[').and_then(|rest| rest.find(']')) else {
+            continue;
+        };
+        let timestamp = &line[1..close + 1];
+        let text = line[close + 2..].trim().to_string();
+        let mut parts = timestamp.splitn(2, ':');
+        let (Some(minutes), Some(seconds)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        let (Ok(minutes), Ok(seconds)) = (minutes.parse::<u32>(), seconds.parse::<f32>()) else {
+            continue;
+        };
+        content.push((minutes * 60_000 + (seconds * 1000.0).round() as u32, text));
     }
+    if content.is_empty() {
+        None
+    } else {
+        Some(content)
+    }
+}
 
-    pub fn to_search_query(&self) -> Vec<(String, String)> {
-        let mut query = Vec::new();
-        query.push(("track_name".to_string(), self.track_name.clone()));
-        if self.artist_name.len() > 0 {
-            query.push(("artist_name".to_string(), self.artist_name.clone()));
-        }
-        if let Some(album_name) = &self.album_name {
-            query.push(("album_name".to_string(), album_name.clone()));
+// writes lyrics found from a provider out according to the configured --embed/--embed-only and
+// sidecar behaviour. a plain `--embed` means "sidecar and embed", so a failed embed (e.g. an
+// unsupported container) must not stop the sidecar from being written.
+pub async fn save_lyrics_for_file(entry: &DirEntry, lyrics: &Lyrics, config: &CliConfig) -> anyhow::Result<()> {
+    let embed_result = if config.embed || config.embed_only {
+        embed_lyrics_for_file(entry, lyrics, config).err()
+    } else {
+        None
+    };
+
+    if !config.embed_only {
+        // a search-fallback hit can come back plain-only (no timed lines); write whatever text
+        // we have rather than silently dropping it, so "found" always means a .lrc landed on disk
+        match lyrics.synced.as_deref().or(lyrics.plain.as_deref()) {
+            Some(text) => write_lrc_for_file(entry, text, config).await?,
+            None => bail!("No lyrics available to write"),
         }
-        query
     }
 
-    pub fn remove_duration(&mut self) {
-        self.duration = None;
+    if let Some(err) = embed_result {
+        if config.embed_only {
+            return Err(err);
+        }
+        tracing::warn!(path = %entry.path().display(), error = %err, "embed_failed");
     }
+    Ok(())
+}
 
-    pub fn remove_album_name(&mut self) {
-        self.album_name = None;
-    }
+/// strips the leading `[mm:ss.xx]` timestamp off each line of LRC text, for submitting a plain
+/// lyrics field when a source only gave us the synced form
+fn strip_lrc_timestamps(lrc: &str) -> String {
+    lrc.lines()
+        .map(|line| {
+            let line = line.trim();
+            match line.strip_prefix('[').and_then(|rest| rest.find(']')) {
+                Some(close) => line[close + 2..].trim(),
+                None => line,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct LrclibItem {
-    pub id: u32,
-    pub trackName: String,
-    pub artistName: String,
-    pub albumName: String,
-    pub duration: f32,
-    pub instrumental: bool,
-    pub plainLyrics: Option<String>,
-    pub syncedLyrics: Option<String>,
+/// uploads lyrics to lrclib so the database improves for everyone, logging success/failure
+/// rather than failing the whole file on a publish error
+async fn publish_lyrics(entry: &DirEntry, track_name: &str, artist_name: &str, album_name: Option<&str>, duration: Option<f32>, lyrics: &Lyrics, publisher: &LrcLibPublisher) {
+    let synced_lyrics = lyrics.synced.as_deref().unwrap_or("");
+    if synced_lyrics.is_empty() {
+        return;
+    }
+    let plain_lyrics = match lyrics.plain.as_deref() {
+        Some(plain) => plain.to_string(),
+        None => strip_lrc_timestamps(synced_lyrics),
+    };
+    let result = publisher
+        .publish(
+            track_name,
+            artist_name,
+            album_name.unwrap_or(""),
+            duration.unwrap_or(0.0).round() as u32,
+            &plain_lyrics,
+            synced_lyrics,
+        )
+        .await;
+    match result {
+        Ok(_) => tracing::info!(path = %entry.path().display(), "published"),
+        Err(err) => tracing::error!(path = %entry.path().display(), error = %err, "publish_error"),
+    }
 }
 
-impl LrcLibClient {
-    pub fn new(url: &str) -> Self {
-        let mut headers = reqwest::header::HeaderMap::new();
-        headers.insert("Lrclib-Client", DEFAULT_USER_AGENT.parse().unwrap());
-        Self {
-            url: "https://lrclib.net".to_string(),
-            client: reqwest::Client::builder().default_headers(headers).user_agent(DEFAULT_USER_AGENT).build().expect("Failed to create reqwest client"),
+fn is_audio_file(entry: &DirEntry) -> bool {
+    for guess in mime_guess::from_path(entry.path()) {
+        if guess.type_() == mime_guess::mime::AUDIO {
+            return true;
         }
     }
+    false
+}
 
-    pub fn set_url(&mut self, url: &str) {
-        self.url = url.to_string();
-    }
+enum FileOutcome {
+    Found,
+    Skipped,
+    NotFound,
+    Error,
+}
 
-    pub async fn get(&self, query: &LrclibQuery) -> anyhow::Result<Option<LrclibItem>> {
-        let url = format!("{}/api/get" ,self.url);
-        let request_builder = self.client.get(url).query(&query.to_get_query());
-        let response = request_builder.send().await?;
-        if response.status().is_success() {
-            let body = response.text().await?;
-            match serde_json::from_str::<LrclibItem>(&body) {
-                Ok(item) => Ok(Some(item)),
-                Err(err) => {
-                    anyhow::bail!("Error parsing lrclib response (did the api schema change?): {}", err);
-                },
-            }
-        } else {
-            if response.status() == reqwest::StatusCode::NOT_FOUND {
-                Ok(None)
-            } else {
-                Err(anyhow::anyhow!("Error getting lrclib item: {}", response.status()))
+/// tries each provider in order, falling through to the next when one returns no match
+#[tracing::instrument(skip(providers, query, config, entry), fields(path = %entry.path().display()))]
+async fn fetch_lyrics(providers: &[Box<dyn LyricsProvider>], query: &mut LrclibQuery, config: &CliConfig, entry: &DirEntry) -> Option<Lyrics> {
+    for provider in providers {
+        match provider.get(query).await {
+            Ok(Some(lyrics)) => return Some(lyrics),
+            Ok(None) => {
+                if config.search {
+                    tracing::debug!(provider = provider.name(), "search_fallback");
+                    // hide artist hack
+                    if config.ignore.contains(&"artist_name".to_string()) || config.ignore.contains(&"artist".to_string()) {
+                        query.artist_name = "".to_string();
+                    }
+                    match provider.search(query).await {
+                        Ok(Some(candidates)) if !candidates.is_empty() => {
+                            tracing::info!(provider = provider.name(), results = candidates.len(), "search_fallback");
+                            return candidates.into_iter().next();
+                        },
+                        Ok(_) => {
+                            tracing::info!(provider = provider.name(), "not_found");
+                        },
+                        Err(err) => {
+                            tracing::error!(provider = provider.name(), error = %err, "error");
+                        }
+                    }
+                } else {
+                    tracing::info!(provider = provider.name(), "not_found");
+                }
+            },
+            Err(err) => {
+                tracing::error!(provider = provider.name(), error = %err, "error");
             }
         }
     }
+    None
+}
 
-    pub async fn search(&self, query: &LrclibQuery) -> anyhow::Result<Option<Vec<LrclibItem>>> {
-        let url = format!("{}/api/search" ,self.url);
-        let request_builder = self.client.get(url).query(&query.to_search_query());
-        let response = request_builder.send().await?;
-        if response.status().is_success() {
-            let body = response.text().await?;
-            match serde_json::from_str::<Vec<LrclibItem>>(&body) {
-                Ok(items) => Ok(Some(items)), 
-                Err(err) => {
-                    anyhow::bail!("Error parsing lrclib response (did the api schema change?): {}", err);
-                },
-            }
-        } else {
-            if response.status() == reqwest::StatusCode::NOT_FOUND {
-                Ok(None)
-            } else {
-                Err(anyhow::anyhow!("Error getting lrclib item: {}", response.status()))
+#[tracing::instrument(skip(providers, musicbrainz, publisher, config), fields(path = %entry.path().display()))]
+async fn process_file(entry: &DirEntry, providers: &[Box<dyn LyricsProvider>], musicbrainz: Option<&MusicBrainzClient>, publisher: Option<&LrcLibPublisher>, config: &CliConfig) -> FileOutcome {
+    let has_existing_lrc = entry.path().with_extension("lrc").exists();
+    if has_existing_lrc && !config.force {
+        tracing::info!("skipped");
+        if config.publish {
+            if let (Some(publisher), Ok(existing_lyrics)) = (publisher, tokio::fs::read_to_string(entry.path().with_extension("lrc")).await) {
+                if let Ok(tag) = Tag::new().read_from_path(entry.path()) {
+                    // a sidecar .lrc isn't necessarily synced: someone may have hand-dropped
+                    // plain lyrics in with a .lrc extension. only submit it as syncedLyrics if
+                    // it actually carries `[mm:ss]` timestamps; otherwise it's not safe to
+                    // publish as synced, so skip it the same way publish_lyrics skips lyrics
+                    // with no synced text.
+                    let lyrics = if parse_lrc_timestamps(&existing_lyrics).is_some() {
+                        Lyrics {
+                            synced: Some(existing_lyrics),
+                            plain: None,
+                            source: "local-lrc".to_string(),
+                        }
+                    } else {
+                        tracing::info!("local_lrc_not_synced");
+                        Lyrics {
+                            synced: None,
+                            plain: Some(existing_lyrics),
+                            source: "local-lrc".to_string(),
+                        }
+                    };
+                    let album_name = tag.album().map(|album| album.title.to_string());
+                    let track_name = tag.title().map(|title| title.to_string()).unwrap_or_default();
+                    let artist_name = tag.artists().map(|artists| artists.join(", ")).unwrap_or_default();
+                    let duration = tag.duration().map(|duration| duration as f32);
+                    publish_lyrics(entry, &track_name, &artist_name, album_name.as_deref(), duration, &lyrics, publisher).await;
+                }
             }
         }
+        return FileOutcome::Skipped;
     }
-}
+    // read file
+    match Tag::new().read_from_path(entry.path()) {
+        Ok(tag) => {
+            let mut album_name: Option<String> = None;
+            let mut track_name = "".to_string();
+            let mut artist_name = "".to_string();
+            if let Some(album) = tag.album() {
+                album_name = Some(album.title.to_string());
+            }
+            if let Some(title) = tag.title() {
+                track_name = title.to_string();
+            }
+            if let Some(artists) = tag.artists() {
+                artist_name = artists.join(", ");
+            }
+            let duration: Option<f32> = match tag.duration() {
+                Some(duration) => Some(duration as f32),
+                None => None,
+            };
+            let mut lrc_query = LrclibQuery {
+                track_name: track_name.clone(),
+                artist_name: artist_name.clone(),
+                album_name: album_name.clone(),
+                duration: duration,
+                tolerance: config.tolerance,
+            };
+            if config.ignore.contains(&"duration".to_string()) {
+                lrc_query.remove_duration();
+            }
+            if config.ignore.contains(&"album_name".to_string()) || config.ignore.contains(&"album".to_string()) {
+                lrc_query.remove_album_name();
+            }
 
-pub async fn write_lrc_for_file(entry: &DirEntry, synced_lyrics: &str, config: &CliConfig) -> anyhow::Result<()> {
-    let lrc_path = entry.path().with_extension("lrc");
+            // a missing album tag alone doesn't make the query unusable for lrclib, and MB's
+            // throttled lookup is much slower than a provider round-trip, so only treat track
+            // name/artist as "too incomplete to try the providers first"
+            let tags_incomplete = lrc_query.track_name.is_empty() || lrc_query.artist_name.is_empty();
+            let mut result = if config.musicbrainz && tags_incomplete {
+                None
+            } else {
+                fetch_lyrics(providers, &mut lrc_query, config, entry).await
+            };
 
-    match File::create(lrc_path).await {
-        Ok(mut file) => {
-            match file.write_all(synced_lyrics.as_bytes()).await {
-                Ok(_) => {
-                    println!("Wrote synced lrc to {}", entry.path().display());
+            if result.is_none() {
+                if let Some(musicbrainz) = musicbrainz {
+                    match musicbrainz.search_recording(&lrc_query, config.tolerance).await {
+                        Ok(Some(canonical)) => {
+                            tracing::info!(track_name = %canonical.track_name, artist_name = %canonical.artist_name, "musicbrainz_enriched");
+                            lrc_query.track_name = canonical.track_name;
+                            lrc_query.artist_name = canonical.artist_name;
+                            if canonical.album_name.is_some() {
+                                lrc_query.album_name = canonical.album_name;
+                            }
+                            if canonical.duration.is_some() {
+                                lrc_query.duration = canonical.duration;
+                            }
+                            result = fetch_lyrics(providers, &mut lrc_query, config, entry).await;
+                        },
+                        Ok(None) => {
+                            tracing::info!("musicbrainz_no_match");
+                        },
+                        Err(err) => {
+                            tracing::error!(error = %err, "musicbrainz_error");
+                        }
+                    }
+                }
+            }
+
+            match result {
+                Some(lyrics) => {
+                    tracing::info!(track_name = %track_name, provider = %lyrics.source, "found");
+                    // only a non-lrclib provider's result is worth contributing back; with no
+                    // second provider shipped yet (see --providers), this never fires today and
+                    // the existing-.lrc path above is the only way --publish actually uploads
+                    if config.publish && lyrics.source != "lrclib" {
+                        if let Some(publisher) = publisher {
+                            publish_lyrics(entry, &lrc_query.track_name, &lrc_query.artist_name, lrc_query.album_name.as_deref(), lrc_query.duration, &lyrics, publisher).await;
+                        }
+                    }
+                    match save_lyrics_for_file(entry, &lyrics, config).await {
+                        Ok(_) => FileOutcome::Found,
+                        Err(err) => {
+                            tracing::error!(error = %err, "error");
+                            FileOutcome::Error
+                        }
+                    }
                 },
-                Err(err) => {
-                    bail!("Writing file failed: {}", err);
+                None => {
+                    tracing::info!("not_found");
+                    FileOutcome::NotFound
                 }
             }
         },
         Err(err) => {
-            bail!("Creating file failed: {}", err);
+            tracing::error!(error = %err, "error");
+            FileOutcome::Error
         }
     }
-
-    Ok(())
 }
 
 #[tokio::main]
 async fn main() {
-    let config = CliConfig::parse();
-    let mut client = LrcLibClient::new(&config.lrclib_url);
-    client.set_url(&config.lrclib_url);
-    for result in WalkBuilder::new(".").hidden(config.hidden).add_custom_ignore_filename(".lrcsyncignore").build() {
-        match result {
-            Ok(entry) => {
-                // check if media file
-                let mut is_audio = false;
-                for guess in mime_guess::from_path(entry.path()) {
-                    if guess.type_() == mime_guess::mime::AUDIO {
-                        is_audio = true;
-                    }
-                }
-                if !is_audio {
-                    continue;
-                }
-                let has_existing_lrc = entry.path().with_extension("lrc").exists();
-                if has_existing_lrc && !config.force {
-                    println!("Skipping {}: lrc file already exists", entry.path().display());
-                    continue;
-                }
-                // read file
-                match Tag::new().read_from_path(entry.path()) {
-                    Ok(tag) => {
-                        let mut album_name: Option<String> = None;
-                        let mut track_name = "".to_string();
-                        let mut artist_name = "".to_string();
-                        if let Some(album) = tag.album() {
-                            album_name = Some(album.title.to_string());
-                        }
-                        if let Some(title) = tag.title() {
-                            track_name = title.to_string();
-                        }
-                        if let Some(artists) = tag.artists() {
-                            artist_name = artists.join(", ");
-                        }
-                        let duration: Option<f32> = match tag.duration() {
-                            Some(duration) => Some(duration as f32),
-                            None => None,
-                        };
-                        let mut lrc_query = LrclibQuery {
-                            track_name: track_name.clone(),
-                            artist_name: artist_name.clone(),
-                            album_name: album_name.clone(),
-                            duration: duration,
-                        };
-                        if config.ignore.contains(&"duration".to_string()) {
-                            lrc_query.remove_duration();
-                        }
-                        if config.ignore.contains(&"album_name".to_string()) || config.ignore.contains(&"album".to_string()) {
-                            lrc_query.remove_album_name();
-                        }
-                        match client.get(&lrc_query).await {
-                            Ok(Some(lrc_item)) => {
-                                if let Some(synced_lyrics) = &lrc_item.syncedLyrics {
-                                    println!("Found synced lrc for {}", entry.path().display());
-                                    // write to file with extension changed to .lrc
-                                    match write_lrc_for_file(&entry, synced_lyrics, &config).await {
-                                        Ok(_) => {},
-                                        Err(err) => {
-                                            println!("Error in saving lrc {}: {}",entry.path().display(), err);
-                                        }
-                                    }
-                                }
-                            },
-                            Ok(None) => {
-                                if config.search {
-                                    // search fallback
-                                    println!("Searching lrc for {}", entry.path().display());
-                                    // hide artist hack
-                                    if config.ignore.contains(&"artist_name".to_string()) || config.ignore.contains(&"artist".to_string()) {
-                                        lrc_query.artist_name = "".to_string();
-                                    }
-                                    match client.search(&lrc_query).await {
-                                        Ok(Some(lrc_items)) => {
-                                            // weird order but it works and avoids too much nesting
-                                            if lrc_items.len() == 0 {
-                                                println!("Did not find lrc for (no results) {}",entry.path().display());
-                                            } else {
-                                                let mut canidates = lrc_items;
-                                                if let Some(duration) = &lrc_query.duration {
-                                                    // sort by closed to target duration
-                                                    canidates.sort_by(|a, b| {
-                                                        let a_duration = a.duration as f32;
-                                                        let b_duration = b.duration as f32;
-                                                        let a_delta = a_duration - duration;
-                                                        let b_delta = b_duration - duration;
-                                                        return a_delta.abs().partial_cmp(&b_delta.abs()).unwrap();
-                                                    }); 
-                                                
-                                                    if config.tolerance > 0.0 {
-                                                        canidates = canidates.into_iter().filter(|item| {
-                                                            let item_duration = item.duration as f32;
-                                                            let delta = item_duration - duration;
-                                                            return delta.abs() < config.tolerance;
-                                                        }).collect();
-                                                    }
-                                                }
-                                                
-                                                println!("Searched lrc (found {}secs vs actual {}secs out of {} filtered results) for {}",canidates[0].duration,lrc_query.duration.unwrap_or(-1.0), canidates.len(), entry.path().display());  
-                                                // write to file with extension changed to .lrc
-                                                // TODO: manual duration tolerance?
-                                                match write_lrc_for_file(&entry, &canidates[0].syncedLyrics.as_ref().unwrap(), &config).await {
-                                                    Ok(_) => {},
-                                                    Err(err) => {
-                                                        println!("Error in saving lrc {}: {}",entry.path().display(), err);
-                                                    }
-                                                }
-                                            }
-                                        },
-                                        Ok(None) => {
-                                            println!("Did not find lrc for {}",entry.path().display()); 
-                                        },
-                                        Err(err) => {
-                                            println!("Error searching lrc for {}: {}",entry.path().display(), err);
-                                        }
-                                    }
-                                } else {
-                                    println!("Did not find lrc for {}",entry.path().display()); 
-                                }
-                            }
-                            Err(err) => {
-                                println!("Error finding lrc for {}: {}",entry.path().display(), err);
-                            }
-                        }
-                    },
-                    Err(err) => {
-                        println!("Error reading file metadata {}: {}",entry.path().display(), err);
-                    }
-                }
+    let config = Arc::new(CliConfig::parse());
+
+    // built up front so the subscriber can route log lines through it, keeping -v/-vv output
+    // from tearing through the progress bar mid-redraw the way baseline's `pb.println` did
+    let multi = MultiProgress::new();
+
+    let level = match config.verbose {
+        0 => tracing::Level::INFO,
+        1 => tracing::Level::DEBUG,
+        _ => tracing::Level::TRACE,
+    };
+    let writer_multi = multi.clone();
+    match config.log_format {
+        LogFormat::Pretty => {
+            let writer_multi = writer_multi.clone();
+            tracing_subscriber::fmt()
+                .with_max_level(level)
+                .with_writer(move || ProgressSafeWriter { multi: writer_multi.clone() })
+                .init();
+        }
+        LogFormat::Json => {
+            tracing_subscriber::fmt()
+                .with_max_level(level)
+                .with_writer(move || ProgressSafeWriter { multi: writer_multi.clone() })
+                .json()
+                .init();
+        }
+    }
+
+    let mut providers = match build_providers(&config.providers, &config.lrclib_url) {
+        Ok(providers) => providers,
+        Err(err) => {
+            tracing::error!(error = %err, "failed to set up lyrics providers");
+            return;
+        }
+    };
+    if !config.no_cache && config.cache_ttl > 0 {
+        let ttl = Duration::from_secs(config.cache_ttl);
+        // every provider shares one on-disk cache file so their stores don't clobber each other
+        let shared_cache = SharedCacheFile::load().await;
+        let mut cached_providers: Vec<Box<dyn LyricsProvider>> = Vec::with_capacity(providers.len());
+        for provider in providers {
+            cached_providers.push(Box::new(CachingProvider::wrap(provider, shared_cache.clone(), ttl)));
+        }
+        providers = cached_providers;
+    }
+    let providers = Arc::new(providers);
+
+    let musicbrainz = if config.musicbrainz {
+        Some(Arc::new(MusicBrainzClient::new()))
+    } else {
+        None
+    };
+
+    let publisher = if config.publish {
+        Some(Arc::new(LrcLibPublisher::new(&config.lrclib_url, config.publish_workers)))
+    } else {
+        None
+    };
+
+    let entries: Vec<DirEntry> = WalkBuilder::new(".")
+        .hidden(config.hidden)
+        .add_custom_ignore_filename(".lrcsyncignore")
+        .build()
+        .filter_map(|result| match result {
+            Ok(entry) => Some(entry),
+            Err(err) => {
+                tracing::warn!(error = %err, "error walking directory tree");
+                None
             }
-            Err(e) => {
-                println!("Error walking: {}", e);
+        })
+        .filter(is_audio_file)
+        .collect();
+
+    let pb = multi.add(ProgressBar::new(entries.len() as u64));
+    pb.set_style(
+        ProgressStyle::with_template("{bar:40.cyan/blue} {pos}/{len} {msg}")
+            .unwrap_or_else(|_| ProgressStyle::default_bar()),
+    );
+
+    let found = Arc::new(AtomicUsize::new(0));
+    let skipped = Arc::new(AtomicUsize::new(0));
+
+    stream::iter(entries.into_iter().map(|entry| {
+        let providers = providers.clone();
+        let musicbrainz = musicbrainz.clone();
+        let publisher = publisher.clone();
+        let config = config.clone();
+        let pb = pb.clone();
+        let found = found.clone();
+        let skipped = skipped.clone();
+        async move {
+            let outcome = process_file(&entry, &providers, musicbrainz.as_deref(), publisher.as_deref(), &config).await;
+            match outcome {
+                FileOutcome::Found => { found.fetch_add(1, Ordering::Relaxed); },
+                FileOutcome::Skipped => { skipped.fetch_add(1, Ordering::Relaxed); },
+                FileOutcome::NotFound | FileOutcome::Error => {},
             }
+            pb.set_message(format!("found {} skipped {}", found.load(Ordering::Relaxed), skipped.load(Ordering::Relaxed)));
+            pb.inc(1);
         }
+    }))
+    .buffer_unordered(config.concurrency.max(1))
+    .collect::<Vec<()>>()
+    .await;
+
+    pb.finish_with_message(format!("found {} skipped {}", found.load(Ordering::Relaxed), skipped.load(Ordering::Relaxed)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_lrc_timestamps_round_trips_mm_ss_and_mm_ss_xx() {
+        let lrc = "[00:01.50] first line\n[01:02] second line\nno timestamp here";
+        let parsed = parse_lrc_timestamps(lrc).unwrap();
+        assert_eq!(parsed, vec![(1500, "first line".to_string()), (62_000, "second line".to_string())]);
+    }
+
+    #[test]
+    fn parse_lrc_timestamps_returns_none_when_nothing_is_timed() {
+        assert!(parse_lrc_timestamps("just plain lyrics\nwith no brackets").is_none());
+    }
+
+    #[test]
+    fn strip_lrc_timestamps_drops_only_the_leading_bracket() {
+        let lrc = "[00:01.50] hello [bracket] world\nno timestamp here";
+        assert_eq!(strip_lrc_timestamps(lrc), "hello [bracket] world\nno timestamp here");
     }
 }
\ No newline at end of file
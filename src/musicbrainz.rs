@@ -0,0 +1,166 @@
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+use crate::providers::LrclibQuery;
+
+static MUSICBRAINZ_USER_AGENT: &str = concat!(
+    env!("CARGO_PKG_NAME"),
+    "/",
+    env!("CARGO_PKG_VERSION"),
+    " (",
+    // github repo
+    env!("CARGO_PKG_HOMEPAGE"),
+    ")"
+);
+
+/// canonical metadata recovered from MusicBrainz, used to rebuild an `LrclibQuery` when the
+/// local tags are missing or wrong
+#[derive(Debug, Clone)]
+pub struct CanonicalMetadata {
+    pub track_name: String,
+    pub artist_name: String,
+    pub album_name: Option<String>,
+    pub duration: Option<f32>,
+}
+
+#[derive(Deserialize)]
+struct RecordingSearchResponse {
+    recordings: Vec<RecordingHit>,
+}
+
+#[derive(Deserialize)]
+struct RecordingHit {
+    title: String,
+    length: Option<u64>,
+    #[serde(rename = "artist-credit")]
+    artist_credit: Option<Vec<ArtistCreditHit>>,
+    releases: Option<Vec<ReleaseHit>>,
+}
+
+#[derive(Deserialize)]
+struct ArtistCreditHit {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct ReleaseHit {
+    id: String,
+    title: String,
+}
+
+#[derive(Deserialize)]
+struct ReleaseLookupResponse {
+    title: String,
+}
+
+/// looks up canonical recording/release metadata on MusicBrainz to fix bad or missing local
+/// tags before retrying a lyrics query. Rate limited to 1 request/sec per MusicBrainz's terms
+/// of use.
+pub struct MusicBrainzClient {
+    client: reqwest::Client,
+    url: String,
+    last_request: Mutex<Option<Instant>>,
+}
+
+impl MusicBrainzClient {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .user_agent(MUSICBRAINZ_USER_AGENT)
+                .build()
+                .expect("Failed to create reqwest client"),
+            url: "https://musicbrainz.org/ws/2".to_string(),
+            last_request: Mutex::new(None),
+        }
+    }
+
+    async fn throttle(&self) {
+        let mut last_request = self.last_request.lock().await;
+        if let Some(previous) = *last_request {
+            let elapsed = previous.elapsed();
+            if elapsed < Duration::from_secs(1) {
+                tokio::time::sleep(Duration::from_secs(1) - elapsed).await;
+            }
+        }
+        *last_request = Some(Instant::now());
+    }
+
+    /// searches for a recording by title/artist/duration, then looks up the matching release to
+    /// get a canonical album title, preferring candidates within `tolerance` seconds of `query.duration`
+    pub async fn search_recording(&self, query: &LrclibQuery, tolerance: f32) -> anyhow::Result<Option<CanonicalMetadata>> {
+        self.throttle().await;
+
+        let mut lucene = format!("recording:\"{}\"", query.track_name);
+        if !query.artist_name.is_empty() {
+            lucene.push_str(&format!(" AND artist:\"{}\"", query.artist_name));
+        }
+        if let Some(duration) = query.duration {
+            let low_ms = ((duration - tolerance).max(0.0) * 1000.0) as i64;
+            let high_ms = ((duration + tolerance) * 1000.0) as i64;
+            lucene.push_str(&format!(" AND dur:[{} TO {}]", low_ms, high_ms));
+        }
+
+        let url = format!("{}/recording", self.url);
+        let response = self
+            .client
+            .get(url)
+            .query(&[("query", lucene.as_str()), ("fmt", "json")])
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!("MusicBrainz recording search failed: {}", response.status());
+        }
+        let body: RecordingSearchResponse = response.json().await?;
+
+        let mut candidates = body.recordings;
+        if let Some(duration) = query.duration {
+            let target_ms = (duration * 1000.0) as i64;
+            candidates.sort_by_key(|recording| {
+                recording.length.map(|length| (length as i64 - target_ms).abs()).unwrap_or(i64::MAX)
+            });
+            let tolerance_ms = (tolerance * 1000.0) as i64;
+            candidates.retain(|recording| {
+                recording.length.map(|length| (length as i64 - target_ms).abs() < tolerance_ms).unwrap_or(true)
+            });
+        }
+
+        let best = match candidates.into_iter().next() {
+            Some(recording) => recording,
+            None => return Ok(None),
+        };
+
+        let artist_name = best
+            .artist_credit
+            .as_ref()
+            .map(|credits| credits.iter().map(|credit| credit.name.clone()).collect::<Vec<_>>().join(", "))
+            .unwrap_or_default();
+
+        let album_name = match best.releases.as_ref().and_then(|releases| releases.first()) {
+            Some(release) => match self.lookup_release_title(&release.id).await {
+                Ok(title) => Some(title),
+                Err(_) => Some(release.title.clone()),
+            },
+            None => None,
+        };
+
+        Ok(Some(CanonicalMetadata {
+            track_name: best.title,
+            artist_name,
+            album_name,
+            duration: best.length.map(|length| length as f32 / 1000.0),
+        }))
+    }
+
+    async fn lookup_release_title(&self, release_id: &str) -> anyhow::Result<String> {
+        self.throttle().await;
+        let url = format!("{}/release/{}", self.url, release_id);
+        let response = self.client.get(url).query(&[("fmt", "json")]).send().await?;
+        if !response.status().is_success() {
+            anyhow::bail!("MusicBrainz release lookup failed: {}", response.status());
+        }
+        let body: ReleaseLookupResponse = response.json().await?;
+        Ok(body.title)
+    }
+}
@@ -0,0 +1,204 @@
+use async_trait::async_trait;
+use serde::{Serialize, Deserialize};
+
+use super::{Lyrics, LyricsProvider};
+
+static DEFAULT_USER_AGENT: &str = concat!(
+    env!("CARGO_PKG_NAME"),
+    "/",
+    env!("CARGO_PKG_VERSION"),
+    " (",
+    // github repo
+    env!("CARGO_PKG_HOMEPAGE"),
+    ")"
+);
+
+pub struct LrcLibClient {
+    pub url: String,
+    pub client: reqwest::Client,
+}
+
+pub struct LrclibQuery {
+    pub track_name: String,
+    pub artist_name: String,
+    pub album_name: Option<String>,
+    pub duration: Option<f32>,
+    pub tolerance: f32,
+}
+
+impl LrclibQuery {
+    // old method
+    pub fn to_get_query_string(&self) -> String {
+        let mut query = String::new();
+        query.push_str("track_name=");
+        query.push_str(&self.track_name);
+        query.push_str("&artist_name=");
+        query.push_str(&self.artist_name);
+        if let Some(album_name) = &self.album_name {
+            query.push_str("&album_name=");
+            query.push_str(album_name);
+        }
+        if let Some(duration) = &self.duration {
+            query.push_str("&duration=");
+            query.push_str(&duration.to_string());
+        }
+        query
+    }
+
+    pub fn to_get_query(&self) -> Vec<(String, String)> {
+        let mut query = self.to_search_query();
+        if let Some(duration) = &self.duration {
+            query.push(("duration".to_string(), duration.to_string()));
+        }
+        query
+    }
+
+    pub fn to_search_query(&self) -> Vec<(String, String)> {
+        let mut query = Vec::new();
+        query.push(("track_name".to_string(), self.track_name.clone()));
+        if self.artist_name.len() > 0 {
+            query.push(("artist_name".to_string(), self.artist_name.clone()));
+        }
+        if let Some(album_name) = &self.album_name {
+            query.push(("album_name".to_string(), album_name.clone()));
+        }
+        query
+    }
+
+    pub fn remove_duration(&mut self) {
+        self.duration = None;
+    }
+
+    pub fn remove_album_name(&mut self) {
+        self.album_name = None;
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LrclibItem {
+    pub id: u32,
+    pub trackName: String,
+    pub artistName: String,
+    pub albumName: String,
+    pub duration: f32,
+    pub instrumental: bool,
+    pub plainLyrics: Option<String>,
+    pub syncedLyrics: Option<String>,
+}
+
+/// an item with neither synced nor plain lyrics (e.g. an instrumental track) isn't a usable hit;
+/// treating it as one would short-circuit the provider fallback chain on an empty match
+fn has_usable_lyrics(item: &LrclibItem) -> bool {
+    item.syncedLyrics.is_some() || item.plainLyrics.is_some()
+}
+
+impl From<&LrclibItem> for Lyrics {
+    fn from(item: &LrclibItem) -> Self {
+        Lyrics {
+            synced: item.syncedLyrics.clone(),
+            plain: item.plainLyrics.clone(),
+            source: "lrclib".to_string(),
+        }
+    }
+}
+
+impl LrcLibClient {
+    pub fn new(url: &str) -> Self {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("Lrclib-Client", DEFAULT_USER_AGENT.parse().unwrap());
+        Self {
+            url: "https://lrclib.net".to_string(),
+            client: reqwest::Client::builder().default_headers(headers).user_agent(DEFAULT_USER_AGENT).build().expect("Failed to create reqwest client"),
+        }
+    }
+
+    pub fn set_url(&mut self, url: &str) {
+        self.url = url.to_string();
+    }
+
+    pub async fn get(&self, query: &LrclibQuery) -> anyhow::Result<Option<LrclibItem>> {
+        let url = format!("{}/api/get" ,self.url);
+        let request_builder = self.client.get(url).query(&query.to_get_query());
+        let response = request_builder.send().await?;
+        if response.status().is_success() {
+            let body = response.text().await?;
+            match serde_json::from_str::<LrclibItem>(&body) {
+                Ok(item) => Ok(Some(item)),
+                Err(err) => {
+                    anyhow::bail!("Error parsing lrclib response (did the api schema change?): {}", err);
+                },
+            }
+        } else {
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                Ok(None)
+            } else {
+                Err(anyhow::anyhow!("Error getting lrclib item: {}", response.status()))
+            }
+        }
+    }
+
+    pub async fn search(&self, query: &LrclibQuery) -> anyhow::Result<Option<Vec<LrclibItem>>> {
+        let url = format!("{}/api/search" ,self.url);
+        let request_builder = self.client.get(url).query(&query.to_search_query());
+        let response = request_builder.send().await?;
+        if response.status().is_success() {
+            let body = response.text().await?;
+            match serde_json::from_str::<Vec<LrclibItem>>(&body) {
+                Ok(items) => Ok(Some(items)),
+                Err(err) => {
+                    anyhow::bail!("Error parsing lrclib response (did the api schema change?): {}", err);
+                },
+            }
+        } else {
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                Ok(None)
+            } else {
+                Err(anyhow::anyhow!("Error getting lrclib item: {}", response.status()))
+            }
+        }
+    }
+
+    /// ranks search candidates closest-duration-first and drops anything outside `query.tolerance`
+    fn rank_candidates(&self, mut candidates: Vec<LrclibItem>, query: &LrclibQuery) -> Vec<LrclibItem> {
+        if let Some(duration) = query.duration {
+            candidates.sort_by(|a, b| {
+                let a_delta = (a.duration - duration).abs();
+                let b_delta = (b.duration - duration).abs();
+                a_delta.partial_cmp(&b_delta).unwrap()
+            });
+            if query.tolerance > 0.0 {
+                candidates.retain(|item| (item.duration - duration).abs() < query.tolerance);
+            }
+        }
+        candidates
+    }
+}
+
+#[async_trait]
+impl LyricsProvider for LrcLibClient {
+    fn name(&self) -> &str {
+        "lrclib"
+    }
+
+    async fn get(&self, query: &LrclibQuery) -> anyhow::Result<Option<Lyrics>> {
+        let item = match LrcLibClient::get(self, query).await? {
+            Some(item) if has_usable_lyrics(&item) => item,
+            // an instrumental hit, or a 404, both mean "nothing here" to the fallback chain
+            _ => return Ok(None),
+        };
+        Ok(Some(Lyrics::from(&item)))
+    }
+
+    async fn search(&self, query: &LrclibQuery) -> anyhow::Result<Option<Vec<Lyrics>>> {
+        let items = match LrcLibClient::search(self, query).await? {
+            Some(items) => items,
+            None => return Ok(None),
+        };
+        let ranked = self.rank_candidates(items, query);
+        let usable: Vec<Lyrics> = ranked.iter().filter(|item| has_usable_lyrics(item)).map(Lyrics::from).collect();
+        if usable.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(usable))
+    }
+}
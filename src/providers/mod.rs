@@ -0,0 +1,48 @@
+pub mod lrclib;
+
+pub use lrclib::{LrcLibClient, LrclibItem, LrclibQuery};
+
+use async_trait::async_trait;
+use serde::{Serialize, Deserialize};
+
+/// a lyrics result normalized from whatever shape a given backend returns, so that
+/// `write_lrc_for_file`/`embed_lyrics_for_file` stay provider-agnostic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lyrics {
+    pub synced: Option<String>,
+    pub plain: Option<String>,
+    pub source: String,
+}
+
+#[async_trait]
+pub trait LyricsProvider: Send + Sync {
+    /// short identifier used in `--providers` and in log output, e.g. "lrclib"
+    fn name(&self) -> &str;
+
+    async fn get(&self, query: &LrclibQuery) -> anyhow::Result<Option<Lyrics>>;
+
+    /// best-match-first candidates for the query, or `None`/empty if nothing matched
+    async fn search(&self, query: &LrclibQuery) -> anyhow::Result<Option<Vec<Lyrics>>>;
+}
+
+/// builds the ordered provider chain requested via `--providers`, skipping (with a warning)
+/// any name we don't have a backend for yet
+pub fn build_providers(names: &[String], lrclib_url: &str) -> anyhow::Result<Vec<Box<dyn LyricsProvider>>> {
+    let mut providers: Vec<Box<dyn LyricsProvider>> = Vec::new();
+    for name in names {
+        match name.trim().to_lowercase().as_str() {
+            "lrclib" => {
+                let mut client = LrcLibClient::new(lrclib_url);
+                client.set_url(lrclib_url);
+                providers.push(Box::new(client));
+            },
+            other => {
+                tracing::warn!(provider = other, "unknown lyrics provider, skipping");
+            }
+        }
+    }
+    if providers.is_empty() {
+        anyhow::bail!("No usable lyrics providers configured (--providers)");
+    }
+    Ok(providers)
+}